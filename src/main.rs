@@ -2,14 +2,14 @@
 
 use std::{
     io::{self, Read, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
     str::FromStr,
 };
 
 #[cfg(not(debug_assertions))]
 use std::panic::catch_unwind;
 
-use chrono::{Local, NaiveDate};
+use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
 use clap::{Parser, Subcommand};
 use crossterm::{
     event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
@@ -23,12 +23,18 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
     Terminal,
 };
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use serde::{Deserialize, Serialize};
 
 #[derive(Parser)]
 struct Args {
     #[clap(long, short)]
     db: Option<PathBuf>,
+    /// Treat this date as "now" instead of asking the system clock.
+    ///
+    /// Accepts `YYYY-MM-DD`. Useful for backfilling a day that isn't today.
+    #[clap(long)]
+    at: Option<String>,
     #[clap(subcommand)]
     action: Option<Action>,
 }
@@ -40,16 +46,352 @@ enum Action {
     Cli,
     Add {
         date: Option<String>,
+        #[clap(long)]
+        category: Option<String>,
     },
     Remove {
         date: Option<String>,
     },
-    List,
+    /// Move an existing entry to a different date.
+    Edit {
+        from: String,
+        to: String,
+    },
+    /// Register a recurring home-office pattern, e.g. `FREQ=WEEKLY;BYDAY=MO,WE`.
+    AddRule {
+        rule: String,
+        until: Option<String>,
+    },
+    List {
+        #[clap(long)]
+        category: Option<String>,
+    },
     DataDir,
-    Export,
+    Export {
+        #[clap(long, value_enum)]
+        format: Option<Format>,
+        #[clap(long)]
+        category: Option<String>,
+    },
+}
+
+/// An export output format, selectable via `--format` or the config's `default_format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Format {
+    #[default]
+    Text,
+    Csv,
+    Json,
+    Ical,
+}
+
+/// User-configurable settings, loaded once from `config.toml` in the data directory.
+#[derive(Debug, Default, Deserialize)]
+struct Config {
+    default_format: Option<Format>,
+}
+
+fn load_config(data_dir: &Path) -> Config {
+    let path = data_dir.join("config.toml");
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| toml::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Everything a command needs that would otherwise come from the environment.
+///
+/// Bundling "now" and the loaded config here means no function reaches for the
+/// system clock directly, which keeps date logic deterministic and testable.
+struct Facts {
+    now: NaiveDate,
+    config: Config,
+}
+
+impl Facts {
+    fn today_string(&self) -> String {
+        self.now.format("%Y-%m-%d").to_string()
+    }
+}
+
+/// A small subset of the iCalendar RRULE grammar: FREQ, INTERVAL, BYDAY and a
+/// terminating COUNT or UNTIL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    freq: Frequency,
+    interval: u32,
+    by_day: Vec<Weekday>,
+    count: Option<u32>,
+    until: Option<NaiveDate>,
+}
+
+fn parse_weekday(code: &str) -> Option<Weekday> {
+    match code {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn parse_rule(rule: &str) -> std::result::Result<Rule, String> {
+    let mut freq = None;
+    let mut interval = 1u32;
+    let mut by_day = Vec::new();
+    let mut count = None;
+    let mut until = None;
+
+    for part in rule.split(';') {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+        let Some((key, value)) = part.split_once('=') else {
+            return Err(format!("invalid RRULE part: {part}"));
+        };
+        match key {
+            "FREQ" => {
+                freq = Some(match value {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    other => return Err(format!("unsupported FREQ: {other}")),
+                });
+            }
+            "INTERVAL" => {
+                interval = value
+                    .parse()
+                    .ok()
+                    .filter(|&n: &u32| n > 0)
+                    .ok_or_else(|| format!("invalid INTERVAL: {value}"))?;
+            }
+            "BYDAY" => {
+                for code in value.split(',') {
+                    by_day.push(
+                        parse_weekday(code)
+                            .ok_or_else(|| format!("invalid BYDAY entry: {code}"))?,
+                    );
+                }
+            }
+            "COUNT" => {
+                count = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid COUNT: {value}"))?,
+                );
+            }
+            "UNTIL" => {
+                until = Some(
+                    NaiveDate::parse_from_str(value, "%Y%m%d")
+                        .map_err(|_| format!("invalid UNTIL: {value}"))?,
+                );
+            }
+            other => return Err(format!("unsupported RRULE key: {other}")),
+        }
+    }
+
+    // BYDAY without FREQ has no frequency to repeat against, so it falls out
+    // here as a missing-FREQ error rather than being silently accepted.
+    let freq = freq.ok_or_else(|| "RRULE is missing FREQ".to_string())?;
+
+    Ok(Rule {
+        freq,
+        interval,
+        by_day,
+        count,
+        until,
+    })
 }
 
-fn interactive_mode(conn: &Connection) -> Result<()> {
+/// Materialize a rule's occurrences from `dtstart` up to `window_end`.
+///
+/// `window_end` clamps unbounded rules (no COUNT/UNTIL) so expansion always
+/// terminates; callers pass the query's upper bound (e.g. "today").
+fn expand_rule(rule: &Rule, dtstart: NaiveDate, window_end: NaiveDate) -> Vec<NaiveDate> {
+    let end = rule.until.map_or(window_end, |until| until.min(window_end));
+
+    let mut occurrences = Vec::new();
+    if dtstart > end {
+        return occurrences;
+    }
+
+    match rule.freq {
+        Frequency::Daily => {
+            let mut current = dtstart;
+            while current <= end {
+                occurrences.push(current);
+                if rule
+                    .count
+                    .is_some_and(|count| occurrences.len() as u32 >= count)
+                {
+                    break;
+                }
+                current += Duration::days(i64::from(rule.interval));
+            }
+        }
+        Frequency::Weekly => {
+            let mut current = dtstart;
+            while current <= end {
+                let week_offset = (current - dtstart).num_days() / 7;
+                if rule.by_day.contains(&current.weekday())
+                    && week_offset % i64::from(rule.interval) == 0
+                {
+                    occurrences.push(current);
+                    if rule
+                        .count
+                        .is_some_and(|count| occurrences.len() as u32 >= count)
+                    {
+                        break;
+                    }
+                }
+                current = current.succ_opt().unwrap();
+            }
+        }
+        Frequency::Monthly => {
+            let mut current = dtstart;
+            let mut month_offset = 0u32;
+            while current <= end {
+                if rule.by_day.contains(&current.weekday())
+                    && month_offset.is_multiple_of(rule.interval)
+                {
+                    occurrences.push(current);
+                    if rule
+                        .count
+                        .is_some_and(|count| occurrences.len() as u32 >= count)
+                    {
+                        break;
+                    }
+                }
+                let next = current.succ_opt().unwrap();
+                if next.month() != current.month() {
+                    month_offset += 1;
+                }
+                current = next;
+            }
+        }
+    }
+
+    occurrences
+}
+
+fn add_rule(
+    conn: &Connection,
+    rule: &str,
+    dtstart: NaiveDate,
+    until: Option<NaiveDate>,
+) -> Result<()> {
+    if let Err(err) = parse_rule(rule) {
+        println!("Error adding rule: {err}");
+        return Ok(());
+    }
+
+    let dtstart = dtstart.format("%Y-%m-%d").to_string();
+    let until = until.map(|u| u.format("%Y-%m-%d").to_string());
+
+    conn.execute(
+        "INSERT INTO home_office_rules (rule, dtstart, until) VALUES (?1, ?2, ?3)",
+        params![rule, dtstart, until],
+    )?;
+    println!("Rule added successfully: {rule}");
+
+    Ok(())
+}
+
+/// Expand every stored rule up to `window_end`, ignoring any that no longer parse.
+fn rule_occurrences(conn: &Connection, window_end: NaiveDate) -> Result<Vec<NaiveDate>> {
+    let mut stmt = conn.prepare("SELECT rule, dtstart, until FROM home_office_rules")?;
+    let rows = stmt.query_map([], |row| {
+        let rule: String = row.get(0)?;
+        let dtstart: String = row.get(1)?;
+        let until: Option<String> = row.get(2)?;
+        Ok((rule, dtstart, until))
+    })?;
+
+    let mut occurrences = Vec::new();
+    for row in rows {
+        let (rule, dtstart, until) = row?;
+        let Ok(parsed) = parse_rule(&rule) else {
+            continue;
+        };
+        let dtstart = NaiveDate::parse_from_str(&dtstart, "%Y-%m-%d").unwrap();
+        let until = until
+            .and_then(|u| NaiveDate::parse_from_str(&u, "%Y-%m-%d").ok())
+            .or(parsed.until);
+        occurrences.extend(expand_rule(&Rule { until, ..parsed }, dtstart, window_end));
+    }
+
+    Ok(occurrences)
+}
+
+/// Explicit `home_office_days` rows, optionally restricted to one category.
+fn explicit_dates(
+    conn: &Connection,
+    category: Option<&str>,
+) -> Result<Vec<(NaiveDate, Option<String>)>> {
+    let mut stmt = conn.prepare(
+        "SELECT date, category FROM home_office_days
+         WHERE ?1 IS NULL OR category = ?1
+         ORDER BY date",
+    )?;
+    let result = stmt
+        .query_map(params![category], |row| {
+            let date: String = row.get(0)?;
+            let category: Option<String> = row.get(1)?;
+            Ok((date, category))
+        })?
+        .map(|row| {
+            let (date, category) = row?;
+            let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|err| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    0,
+                    rusqlite::types::Type::Text,
+                    Box::new(err),
+                )
+            })?;
+            Ok((date, category))
+        })
+        .collect();
+    result
+}
+
+/// The full set of home-office days, tagged with their category: explicit
+/// rows unioned with every recurring rule's occurrences (bounded to
+/// `facts.now`), deduplicated. Recurring rules carry no category, so a
+/// category filter only ever matches explicit rows.
+fn tagged_dates(
+    conn: &Connection,
+    facts: &Facts,
+    category: Option<&str>,
+) -> Result<Vec<(NaiveDate, Option<String>)>> {
+    let mut dates = explicit_dates(conn, category)?;
+
+    if category.is_none() {
+        dates.extend(
+            rule_occurrences(conn, facts.now)?
+                .into_iter()
+                .map(|date| (date, None)),
+        );
+    }
+
+    dates.sort_by_key(|(date, _)| *date);
+    dates.dedup_by_key(|(date, _)| *date);
+
+    Ok(dates)
+}
+
+fn interactive_mode(conn: &Connection, facts: &Facts) -> Result<()> {
     println!("Home Office Tracker");
     println!("1. Add (t)oday's home office day (default)");
     println!("2. (A)dd a specific home office day");
@@ -71,31 +413,30 @@ fn interactive_mode(conn: &Connection) -> Result<()> {
     println!();
 
     match input.to_ascii_lowercase() {
-        '\r' | '\n' | '1' | 't' => add_today(conn)?,
-        '2' | 'a' => add_specific_date(conn)?,
-        '3' | 'l' => list_dates(conn)?,
+        '\r' | '\n' | '1' | 't' => add_today(conn, facts)?,
+        '2' | 'a' => add_specific_date(conn, facts)?,
+        '3' | 'l' => list_dates(conn, facts, None)?,
         '4' | 'd' => delete_home_office_day(conn)?,
-        '5' | 'e' => export_dates(conn)?,
+        '5' | 'e' => export_dates(
+            conn,
+            facts,
+            facts.config.default_format.unwrap_or_default(),
+            None,
+        )?,
         _ => println!("Invalid option."),
     }
 
     Ok(())
 }
 
-fn parse_dates_or_default(input: Option<String>) -> Vec<NaiveDate> {
+fn parse_dates_or_default(input: Option<String>, facts: &Facts) -> Vec<NaiveDate> {
     input.map_or_else(
-        || {
-            vec![NaiveDate::parse_from_str(
-                &Local::now().format("%Y-%m-%d").to_string(),
-                "%Y-%m-%d",
-            )
-            .unwrap()]
-        },
+        || vec![facts.now],
         |i| {
             let v: Vec<_> = i
                 .split("::")
                 .map(|date| {
-                    NaiveDate::parse_from_str(dbg!(date).trim(), "%Y-%m-%d").unwrap_or_else(|_| {
+                    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").unwrap_or_else(|_| {
                         NaiveDate::parse_from_str(date.trim(), "%d.%m.%Y").unwrap()
                     })
                 })
@@ -123,7 +464,7 @@ fn parse_dates_or_default(input: Option<String>) -> Vec<NaiveDate> {
 }
 
 fn run() -> Result<()> {
-    let Args { action, db } = Args::parse();
+    let Args { action, db, at } = Args::parse();
 
     let data_dir = if let Some(db) = db {
         db
@@ -140,21 +481,58 @@ fn run() -> Result<()> {
     // Initialize SQLite database
     let conn = Connection::open(&db_path)?;
     create_table(&conn)?;
+    run_migrations(&conn)?;
+
+    let now = at.as_deref().map_or_else(
+        || {
+            NaiveDate::parse_from_str(&Local::now().format("%Y-%m-%d").to_string(), "%Y-%m-%d")
+                .unwrap()
+        },
+        |at| {
+            NaiveDate::parse_from_str(at, "%Y-%m-%d")
+                .unwrap_or_else(|_| panic!("Invalid --at date: {at}"))
+        },
+    );
+    let facts = Facts {
+        now,
+        config: load_config(&data_dir),
+    };
 
     match action.unwrap_or_default() {
         Action::Tui => {
-            run_tui(conn).unwrap();
+            run_tui(conn, &facts).unwrap();
             Ok(())
         }
-        Action::Export => export_dates(&conn),
+        Action::Export { format, category } => {
+            let format = format.or(facts.config.default_format).unwrap_or_default();
+            export_dates(&conn, &facts, format, category.as_deref())
+        }
         Action::DataDir => {
             println!("{}", data_dir.display());
             Ok(())
         }
-        Action::Cli => interactive_mode(&conn),
-        Action::List => list_dates(&conn),
-        Action::Add { date } => add_dates(&conn, &parse_dates_or_default(date)),
-        Action::Remove { date } => remove_dates(&conn, &parse_dates_or_default(date)),
+        Action::Cli => interactive_mode(&conn, &facts),
+        Action::List { category } => list_dates(&conn, &facts, category.as_deref()),
+        Action::Add { date, category } => add_dates(
+            &conn,
+            &parse_dates_or_default(date, &facts),
+            category.as_deref(),
+        ),
+        Action::Remove { date } => remove_dates(&conn, &parse_dates_or_default(date, &facts)),
+        Action::Edit { from, to } => {
+            let from = NaiveDate::parse_from_str(&from, "%Y-%m-%d")
+                .unwrap_or_else(|_| panic!("Invalid from date: {from}"));
+            let to = NaiveDate::parse_from_str(&to, "%Y-%m-%d")
+                .unwrap_or_else(|_| panic!("Invalid to date: {to}"));
+            edit_date(&conn, from, to)
+        }
+        Action::AddRule { rule, until } => {
+            let until = until.map(|u| {
+                NaiveDate::parse_from_str(&u, "%Y-%m-%d")
+                    .unwrap_or_else(|_| panic!("Invalid --until date: {u}"))
+            });
+            add_rule(&conn, &rule, facts.now, until)
+        }
     }
 }
 
@@ -183,25 +561,109 @@ fn main() -> Result<()> {
 fn create_table(conn: &Connection) -> Result<()> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS home_office_days (
-            date TEXT PRIMARY KEY
+            date TEXT PRIMARY KEY,
+            category TEXT
         )",
         [],
     )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS home_office_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            rule TEXT NOT NULL,
+            dtstart TEXT NOT NULL,
+            until TEXT
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS meta (
+            key TEXT PRIMARY KEY,
+            value TEXT
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn schema_version(conn: &Connection) -> Result<i64> {
+    let version: Option<String> = conn
+        .query_row(
+            "SELECT value FROM meta WHERE key = 'database_version'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    Ok(version.and_then(|v| v.parse().ok()).unwrap_or(0))
+}
+
+fn set_schema_version(conn: &Connection, version: i64) -> Result<()> {
+    conn.execute(
+        "INSERT INTO meta (key, value) VALUES ('database_version', ?1)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![version.to_string()],
+    )?;
+    Ok(())
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(std::result::Result::ok)
+        .any(|name| name == column);
+    Ok(exists)
+}
+
+/// Version 0 -> 1: older databases predate the `category` column that
+/// `create_table` now creates fresh databases with directly.
+fn migration_add_category_column(conn: &Connection) -> Result<()> {
+    if !has_column(conn, "home_office_days", "category")? {
+        conn.execute("ALTER TABLE home_office_days ADD COLUMN category TEXT", [])?;
+    }
+    Ok(())
+}
+
+type Migration = fn(&Connection) -> Result<()>;
+
+/// Ordered, idempotent migration steps. Step `i` brings the schema from
+/// version `i` to version `i + 1`. A database with no `database_version` row
+/// (including one that predates `meta` entirely; `create_table` has already
+/// created `meta` by this point) is treated as version 0 and walks every
+/// step below.
+const MIGRATIONS: &[Migration] = &[migration_add_category_column];
+
+/// Bring an existing database up to the latest schema, one transaction per step.
+fn run_migrations(conn: &Connection) -> Result<()> {
+    let mut current = schema_version(conn)?;
+
+    for migration in MIGRATIONS.iter().skip(current as usize) {
+        let tx = conn.unchecked_transaction()?;
+        migration(&tx)?;
+        tx.commit()?;
+        current += 1;
+        set_schema_version(conn, current)?;
+    }
+
+    if current == 0 {
+        set_schema_version(conn, 0)?;
+    }
+
     Ok(())
 }
 
-fn add_dates(conn: &Connection, date: &[NaiveDate]) -> Result<()> {
+fn add_dates(conn: &Connection, date: &[NaiveDate], category: Option<&str>) -> Result<()> {
     for date in date {
-        add_date(conn, *date)?;
+        add_date(conn, *date, category)?;
     }
     Ok(())
 }
 
-fn add_date(conn: &Connection, date: NaiveDate) -> Result<()> {
+fn add_date(conn: &Connection, date: NaiveDate, category: Option<&str>) -> Result<()> {
     let date = date.format("%Y-%m-%d").to_string();
     match conn.execute(
-        "INSERT INTO home_office_days (date) VALUES (?1)",
-        params![date],
+        "INSERT INTO home_office_days (date, category) VALUES (?1, ?2)",
+        params![date, category],
     ) {
         Ok(_) => println!("Date added successfully: {date}"),
         Err(err) => {
@@ -218,17 +680,12 @@ fn add_date(conn: &Connection, date: NaiveDate) -> Result<()> {
     Ok(())
 }
 
-fn add_today(conn: &Connection) -> Result<()> {
-    add_date(
-        conn,
-        NaiveDate::parse_from_str(&Local::now().format("%Y-%m-%d").to_string(), "%Y-%m-%d")
-            .unwrap(),
-    )
+fn add_today(conn: &Connection, facts: &Facts) -> Result<()> {
+    add_date(conn, facts.now, None)
 }
 
-fn add_specific_date(conn: &Connection) -> Result<()> {
-    let today = Local::now();
-    let today_string = today.format("%Y-%m-%d").to_string();
+fn add_specific_date(conn: &Connection, facts: &Facts) -> Result<()> {
+    let today_string = facts.today_string();
 
     print!("Enter a date (YYYY-MM-DD) or press Enter to use today [{today_string}]: ");
     io::stdout().flush().unwrap();
@@ -238,7 +695,7 @@ fn add_specific_date(conn: &Connection) -> Result<()> {
     let input = input.trim();
 
     let date_to_add = if input.is_empty() {
-        NaiveDate::parse_from_str(&today.format("%Y-%m-%d").to_string(), "%Y-%m-%d").unwrap()
+        facts.now
     } else {
         let date = NaiveDate::parse_from_str(input, "%Y-%m-%d");
         if let Ok(date) = date {
@@ -249,19 +706,16 @@ fn add_specific_date(conn: &Connection) -> Result<()> {
         }
     };
 
-    add_date(conn, date_to_add)
+    add_date(conn, date_to_add, None)
 }
 
-fn list_dates(conn: &Connection) -> Result<()> {
-    let mut stmt = conn.prepare("SELECT date FROM home_office_days ORDER BY date")?;
-    let rows = stmt.query_map([], |row| {
-        let date: String = row.get(0)?;
-        Ok(date)
-    })?;
-
+fn list_dates(conn: &Connection, facts: &Facts, category: Option<&str>) -> Result<()> {
     println!("Home Office Days:");
-    for row in rows {
-        println!("{}", row?);
+    for (date, category) in tagged_dates(conn, facts, category)? {
+        match category {
+            Some(category) => println!("{} @{category}", date.format("%Y-%m-%d")),
+            None => println!("{}", date.format("%Y-%m-%d")),
+        }
     }
 
     Ok(())
@@ -299,109 +753,279 @@ fn delete_home_office_day(conn: &Connection) -> Result<()> {
     remove_date(conn, NaiveDate::parse_from_str(input, "%Y-%m-%d").unwrap())
 }
 
-fn get_export(conn: &Connection) -> Result<Vec<String>> {
-    let mut stmt = conn.prepare("SELECT date FROM home_office_days ORDER BY date")?;
-    let rows: Vec<NaiveDate> = stmt
-        .query_map([], |row| {
-            let date: String = row.get(0)?;
-            NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|err| {
-                rusqlite::Error::FromSqlConversionFailure(
-                    0,
-                    rusqlite::types::Type::Text,
-                    Box::new(err),
-                )
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
+/// Relocate an existing entry in place instead of a delete-then-add dance.
+fn edit_date(conn: &Connection, from: NaiveDate, to: NaiveDate) -> Result<()> {
+    let from = from.format("%Y-%m-%d").to_string();
+    let to = to.format("%Y-%m-%d").to_string();
 
-    if rows.is_empty() {
-        return Ok(Vec::new());
+    let tx = conn.unchecked_transaction()?;
+    let updated = match tx.execute(
+        "UPDATE home_office_days SET date = ?2 WHERE date = ?1",
+        params![from, to],
+    ) {
+        Ok(updated) => updated,
+        Err(err) => {
+            if let Some(sqlite_error) = err.sqlite_error() {
+                if sqlite_error.code == rusqlite::ErrorCode::ConstraintViolation {
+                    println!("Error editing date: {to} is already a home office day.");
+                    return Ok(());
+                }
+            }
+            println!("Error editing date: {err}");
+            return Ok(());
+        }
+    };
+
+    if updated == 0 {
+        println!("Error editing date: {from} is not a home office day.");
+        return Ok(());
+    }
+
+    tx.commit()?;
+    println!("Date edited successfully: {from} -> {to}");
+    Ok(())
+}
+
+/// Collapse a sorted, deduplicated, tagged date list into contiguous
+/// `(start, end)` ranges. Consecutive dates only merge when they share the
+/// same category, so a per-category export can't bleed into its neighbor.
+fn merge_ranges(
+    dates: &[(NaiveDate, Option<String>)],
+) -> Vec<(NaiveDate, NaiveDate, Option<String>)> {
+    if dates.is_empty() {
+        return Vec::new();
     }
 
     let mut ranges = Vec::new();
-    let mut start = rows[0];
-    let mut end = rows[0];
+    let mut start = dates[0].0;
+    let mut end = dates[0].0;
+    let mut category = &dates[0].1;
 
-    for date in &rows[1..] {
+    for (date, date_category) in &dates[1..] {
         if *date
             == end
                 .succ_opt()
                 .expect("I'm probably not alive anymore at this point.")
+            && date_category == category
         {
             end = *date;
         } else {
-            ranges.push((start, end));
+            ranges.push((start, end, category.clone()));
             start = *date;
             end = *date;
+            category = date_category;
         }
     }
-    ranges.push((start, end));
+    ranges.push((start, end, category.clone()));
 
-    let mut result = Vec::new();
+    ranges
+}
 
-    for (start, end) in ranges {
-        if start == end {
-            result.push(format!("{}", start.format("%Y-%m-%d")));
-        } else {
-            result.push(format!(
-                "{} :: {}",
-                start.format("%Y-%m-%d"),
-                end.format("%Y-%m-%d")
-            ));
-        }
+fn format_text_range(start: NaiveDate, end: NaiveDate) -> String {
+    if start == end {
+        format!("{}", start.format("%Y-%m-%d"))
+    } else {
+        format!("{} :: {}", start.format("%Y-%m-%d"), end.format("%Y-%m-%d"))
     }
+}
+
+/// Merged ranges paired with their category, formatted for display. Used by
+/// the TUI, which needs the category alongside each entry without baking it
+/// into the plain date string that drives deletion/edit parsing.
+fn get_export_rows(
+    conn: &Connection,
+    facts: &Facts,
+    category: Option<&str>,
+) -> Result<Vec<(String, Option<String>)>> {
+    let rows = tagged_dates(conn, facts, category)?;
+
+    Ok(merge_ranges(&rows)
+        .into_iter()
+        .map(|(start, end, category)| (format_text_range(start, end), category))
+        .collect())
+}
 
-    Ok(result)
+fn get_export(conn: &Connection, facts: &Facts, category: Option<&str>) -> Result<Vec<String>> {
+    Ok(get_export_rows(conn, facts, category)?
+        .into_iter()
+        .map(|(date, _)| date)
+        .collect())
 }
 
-fn export_dates(conn: &Connection) -> Result<()> {
-    let export = get_export(conn)?;
-    for v in export {
-        println!("{v}");
+fn format_csv(dates: &[(NaiveDate, Option<String>)]) -> String {
+    let mut out = String::from("date\n");
+    for (date, _) in dates {
+        out.push_str(&date.format("%Y-%m-%d").to_string());
+        out.push('\n');
     }
+    out
+}
+
+#[derive(Serialize)]
+struct JsonRange {
+    start: String,
+    end: String,
+}
+
+fn format_json(ranges: &[(NaiveDate, NaiveDate)]) -> String {
+    let ranges: Vec<JsonRange> = ranges
+        .iter()
+        .map(|&(start, end)| JsonRange {
+            start: start.format("%Y-%m-%d").to_string(),
+            end: end.format("%Y-%m-%d").to_string(),
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&ranges).unwrap()
+}
+
+/// Build a VCALENDAR with one all-day VEVENT per range. iCal's DTEND is
+/// exclusive, so it's set to the day after the range's last day.
+fn format_ical(ranges: &[(NaiveDate, NaiveDate)]) -> String {
+    let mut out = String::new();
+    out.push_str("BEGIN:VCALENDAR\r\n");
+    out.push_str("VERSION:2.0\r\n");
+    out.push_str("PRODID:-//home-office-tracker//EN\r\n");
+
+    for (i, &(start, end)) in ranges.iter().enumerate() {
+        let dtend = end
+            .succ_opt()
+            .expect("I'm probably not alive anymore at this point.");
+        out.push_str("BEGIN:VEVENT\r\n");
+        out.push_str(&format!(
+            "UID:{}-{i}@home-office-tracker\r\n",
+            start.format("%Y%m%d")
+        ));
+        out.push_str(&format!(
+            "DTSTART;VALUE=DATE:{}\r\n",
+            start.format("%Y%m%d")
+        ));
+        out.push_str(&format!("DTEND;VALUE=DATE:{}\r\n", dtend.format("%Y%m%d")));
+        out.push_str("SUMMARY:Home Office\r\n");
+        out.push_str("END:VEVENT\r\n");
+    }
+
+    out.push_str("END:VCALENDAR\r\n");
+    out
+}
+
+fn format_export(dates: &[(NaiveDate, Option<String>)], format: Format) -> String {
+    let ranges = merge_ranges(dates);
+    match format {
+        Format::Text => ranges
+            .into_iter()
+            .map(|(start, end, _)| format_text_range(start, end))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Format::Csv => format_csv(dates),
+        Format::Json => {
+            let ranges: Vec<_> = ranges
+                .into_iter()
+                .map(|(start, end, _)| (start, end))
+                .collect();
+            format_json(&ranges)
+        }
+        Format::Ical => {
+            let ranges: Vec<_> = ranges
+                .into_iter()
+                .map(|(start, end, _)| (start, end))
+                .collect();
+            format_ical(&ranges)
+        }
+    }
+}
+
+fn export_dates(
+    conn: &Connection,
+    facts: &Facts,
+    format: Format,
+    category: Option<&str>,
+) -> Result<()> {
+    let dates = tagged_dates(conn, facts, category)?;
+    println!("{}", format_export(&dates, format));
     Ok(())
 }
 
+/// Split TUI input like `2025-01-03 @home` into its date portion and an
+/// optional trailing `@category`.
+fn split_category(input: &str) -> (&str, Option<&str>) {
+    input
+        .rsplit_once('@')
+        .map_or((input.trim(), None), |(dates, category)| {
+            (
+                dates.trim(),
+                Some(category.trim()).filter(|c| !c.is_empty()),
+            )
+        })
+}
+
 #[derive(PartialEq, Eq, Copy, Clone)]
 enum InputMode {
     Add,
     Remove,
+    Filter,
+    Edit,
 }
 
-struct AppState {
+struct AppState<'a> {
     conn: Connection,
+    facts: &'a Facts,
     dates: Vec<String>,
+    /// Category for each entry in `dates`, kept in lockstep purely for
+    /// display; parsing (remove/edit) only ever sees the plain date in `dates`.
+    categories: Vec<Option<String>>,
     selected_index: usize,
     input_box: Option<String>,
     input_mode: InputMode,
+    category_filter: Option<String>,
+    edit_from: Option<NaiveDate>,
 }
 
-impl AppState {
-    fn new(conn: Connection) -> Self {
-        let export = get_export(&conn).unwrap();
+impl<'a> AppState<'a> {
+    fn new(conn: Connection, facts: &'a Facts) -> Self {
+        let export = get_export_rows(&conn, facts, None).unwrap();
+        let (dates, categories) = export.into_iter().unzip();
         Self {
             conn,
-            dates: export,
+            facts,
+            dates,
+            categories,
             selected_index: 0,
             input_box: None,
             input_mode: InputMode::Add,
+            category_filter: None,
+            edit_from: None,
         }
     }
 
     fn update(&mut self) {
-        let export = get_export(&self.conn).unwrap();
-        self.dates = export;
+        let export =
+            get_export_rows(&self.conn, self.facts, self.category_filter.as_deref()).unwrap();
+        let (dates, categories) = export.into_iter().unzip();
+        self.dates = dates;
+        self.categories = categories;
     }
 
     fn start_input(&mut self, input_mode: InputMode) {
         self.input_mode = input_mode;
-        self.input_box = Some(if self.input_mode == InputMode::Add {
-            Local::now().format("%Y-%m-%d").to_string()
-        } else {
-            self.dates
+        self.input_box = Some(match self.input_mode {
+            InputMode::Add => self.facts.today_string(),
+            InputMode::Remove => self
+                .dates
                 .get(self.selected_index)
                 .cloned()
-                .unwrap_or_default()
+                .unwrap_or_default(),
+            InputMode::Filter => self.category_filter.clone().unwrap_or_default(),
+            InputMode::Edit => {
+                let selected = self
+                    .dates
+                    .get(self.selected_index)
+                    .cloned()
+                    .unwrap_or_default();
+                let from = selected.split("::").next().unwrap_or(&selected).trim();
+                self.edit_from = NaiveDate::parse_from_str(from, "%Y-%m-%d").ok();
+                from.to_string()
+            }
         });
     }
 
@@ -410,12 +1034,33 @@ impl AppState {
     }
 
     fn add_string(&mut self, input: String) {
-        add_dates(&self.conn, &parse_dates_or_default(Some(input))).unwrap();
+        let (dates, category) = split_category(&input);
+        add_dates(
+            &self.conn,
+            &parse_dates_or_default(Some(dates.to_string()), self.facts),
+            category,
+        )
+        .unwrap();
         self.update();
     }
 
     fn remove_selected_string(&mut self, input: String) {
-        remove_dates(&self.conn, &parse_dates_or_default(Some(input))).unwrap();
+        remove_dates(&self.conn, &parse_dates_or_default(Some(input), self.facts)).unwrap();
+        self.update();
+    }
+
+    fn edit_selected_string(&mut self, input: String) {
+        if let Some(from) = self.edit_from.take() {
+            let to = NaiveDate::parse_from_str(input.trim(), "%Y-%m-%d")
+                .unwrap_or_else(|_| panic!("Invalid date: {input}"));
+            edit_date(&self.conn, from, to).unwrap();
+            self.update();
+        }
+    }
+
+    fn set_category_filter(&mut self, input: String) {
+        let input = input.trim();
+        self.category_filter = (!input.is_empty()).then(|| input.to_string());
         self.update();
     }
 
@@ -433,12 +1078,12 @@ impl AppState {
 }
 
 #[allow(clippy::too_many_lines)]
-fn run_tui(conn: Connection) -> anyhow::Result<()> {
+fn run_tui(conn: Connection, facts: &Facts) -> anyhow::Result<()> {
     let stdout = io::stdout();
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut state = AppState::new(conn);
+    let mut state = AppState::new(conn, facts);
     terminal.clear()?;
     enable_raw_mode().unwrap();
 
@@ -462,32 +1107,46 @@ fn run_tui(conn: Connection) -> anyhow::Result<()> {
             let items: Vec<ListItem> = state
                 .dates
                 .iter()
+                .zip(&state.categories)
                 .enumerate()
-                .map(|(i, s)| {
+                .map(|(i, (date, category))| {
                     let style = if i == state.selected_index {
                         Style::default().add_modifier(Modifier::BOLD)
                     } else {
                         Style::default()
                     };
-                    ListItem::new(Span::styled(s.clone(), style))
+                    let label = match category {
+                        Some(category) => format!("{date} @{category}"),
+                        None => date.clone(),
+                    };
+                    ListItem::new(Span::styled(label, style))
                 })
                 .collect();
 
-            let list =
-                List::new(items).block(Block::default().borders(Borders::ALL).title("Strings"));
+            let list_title = state.category_filter.as_deref().map_or_else(
+                || "Strings".to_string(),
+                |category| format!("Strings (@{category})"),
+            );
+            let list = List::new(items)
+                .block(Block::default().borders(Borders::ALL).title(list_title));
             f.render_widget(list, left_chunks[0]);
 
             // Render the input box
             if let Some(ref input) = state.input_box {
+                let input_title = match state.input_mode {
+                    InputMode::Filter => "Filter by category",
+                    InputMode::Edit => "Edit date",
+                    InputMode::Add | InputMode::Remove => "Input",
+                };
                 let input_paragraph = Paragraph::new(input.clone())
-                    .block(Block::default().borders(Borders::ALL).title("Input"));
+                    .block(Block::default().borders(Borders::ALL).title(input_title));
                 f.render_widget(input_paragraph, left_chunks[1]);
             }
 
             const NL: &str = "\n- ";
 
             // Render the help box
-            let help_paragraph = Paragraph::new(format!("Keybindings:{NL}Enter to add the current day{NL}A to add a specific day{NL}D to delete the selected day{NL}Esc or Q to exit"))
+            let help_paragraph = Paragraph::new(format!("Keybindings:{NL}Enter to add the current day{NL}A to add a specific day{NL}D to delete the selected day{NL}E to edit the selected day{NL}F to filter by category{NL}Esc or Q to exit"))
                 .wrap(Wrap { trim: true })
                 .block(Block::default().borders(Borders::ALL).title("Help"));
             f.render_widget(help_paragraph, chunks[1]);
@@ -509,13 +1168,15 @@ fn run_tui(conn: Connection) -> anyhow::Result<()> {
                         }
                         KeyCode::Enter => {
                             if let Some(input) = state.take_input() {
-                                if input.trim().is_empty() {
+                                if input.trim().is_empty() && state.input_mode != InputMode::Filter
+                                {
                                     continue;
                                 }
-                                if state.input_mode == InputMode::Add {
-                                    state.add_string(input);
-                                } else {
-                                    state.remove_selected_string(input);
+                                match state.input_mode {
+                                    InputMode::Add => state.add_string(input),
+                                    InputMode::Remove => state.remove_selected_string(input),
+                                    InputMode::Filter => state.set_category_filter(input),
+                                    InputMode::Edit => state.edit_selected_string(input),
                                 }
                             }
                         }
@@ -538,13 +1199,17 @@ fn run_tui(conn: Connection) -> anyhow::Result<()> {
                     KeyCode::Char('a') => {
                         state.start_input(InputMode::Add);
                     }
-                    KeyCode::Char('d') => {
-                        if !state.dates.is_empty() {
-                            state.start_input(InputMode::Remove);
-                        }
+                    KeyCode::Char('d') if !state.dates.is_empty() => {
+                        state.start_input(InputMode::Remove);
+                    }
+                    KeyCode::Char('f') => {
+                        state.start_input(InputMode::Filter);
+                    }
+                    KeyCode::Char('e') if !state.dates.is_empty() => {
+                        state.start_input(InputMode::Edit);
                     }
                     KeyCode::Enter => {
-                        state.add_string(Local::now().format("%Y-%m-%d").to_string());
+                        state.add_string(state.facts.today_string());
                         state.update();
                     }
                     KeyCode::Up => {
@@ -574,9 +1239,17 @@ mod test {
     fn setup_test_db() -> Connection {
         let conn = Connection::open_in_memory().unwrap();
         create_table(&conn).unwrap();
+        run_migrations(&conn).unwrap();
         conn
     }
 
+    fn facts_on(date: &str) -> Facts {
+        Facts {
+            now: NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            config: Config::default(),
+        }
+    }
+
     #[test]
     fn test_create_table() {
         let conn = setup_test_db();
@@ -596,7 +1269,7 @@ mod test {
         let conn = setup_test_db();
         let date = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
 
-        add_date(&conn, date).unwrap();
+        add_date(&conn, date, None).unwrap();
 
         let result: String = conn
             .query_row("SELECT date FROM home_office_days", [], |row| row.get(0))
@@ -610,7 +1283,7 @@ mod test {
         let conn = setup_test_db();
         let date = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
 
-        add_date(&conn, date).unwrap();
+        add_date(&conn, date, None).unwrap();
         remove_date(&conn, date).unwrap();
 
         let count: usize = conn
@@ -622,17 +1295,75 @@ mod test {
         assert_eq!(count, 0);
     }
 
+    #[test]
+    fn test_edit_date_moves_entry() {
+        let conn = setup_test_db();
+        let from = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2025, 1, 5).unwrap();
+
+        add_date(&conn, from, Some("home")).unwrap();
+        edit_date(&conn, from, to).unwrap();
+
+        let (date, category): (String, Option<String>) = conn
+            .query_row("SELECT date, category FROM home_office_days", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+
+        assert_eq!(date, "2025-01-05");
+        assert_eq!(category.as_deref(), Some("home"));
+    }
+
+    #[test]
+    fn test_edit_date_missing_source_does_not_panic() {
+        let conn = setup_test_db();
+        let from = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2025, 1, 5).unwrap();
+
+        edit_date(&conn, from, to).unwrap();
+
+        let count: usize = conn
+            .query_row("SELECT COUNT(*) FROM home_office_days", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_edit_date_target_collision_does_not_panic() {
+        let conn = setup_test_db();
+        let from = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2025, 1, 5).unwrap();
+
+        add_date(&conn, from, None).unwrap();
+        add_date(&conn, to, None).unwrap();
+        edit_date(&conn, from, to).unwrap();
+
+        let dates: Vec<String> = conn
+            .prepare("SELECT date FROM home_office_days ORDER BY date")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+
+        assert_eq!(dates, vec!["2025-01-01", "2025-01-05"]);
+    }
+
     #[test]
     fn test_list_dates() {
         let conn = setup_test_db();
         let date1 = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
         let date2 = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
 
-        add_date(&conn, date1).unwrap();
-        add_date(&conn, date2).unwrap();
+        add_date(&conn, date1, None).unwrap();
+        add_date(&conn, date2, None).unwrap();
 
+        let facts = facts_on("2025-06-01");
         let mut output = Vec::new();
-        list_dates(&conn).unwrap();
+        list_dates(&conn, &facts, None).unwrap();
 
         conn.prepare("SELECT date FROM home_office_days ORDER BY date")
             .unwrap()
@@ -645,16 +1376,18 @@ mod test {
 
     #[test]
     fn test_parse_dates_or_default_single_date() {
+        let facts = facts_on("2025-06-01");
         let input = Some("2025-01-01".to_string());
-        let dates = parse_dates_or_default(input);
+        let dates = parse_dates_or_default(input, &facts);
 
         assert_eq!(dates, vec![NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()]);
     }
 
     #[test]
     fn test_parse_dates_or_default_range() {
+        let facts = facts_on("2025-06-01");
         let input = Some("2025-01-01::2025-01-03".to_string());
-        let dates = parse_dates_or_default(input);
+        let dates = parse_dates_or_default(input, &facts);
 
         assert_eq!(
             dates,
@@ -666,15 +1399,257 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_parse_dates_or_default_uses_facts_now() {
+        let facts = facts_on("2025-03-14");
+        let dates = parse_dates_or_default(None, &facts);
+
+        assert_eq!(dates, vec![NaiveDate::from_ymd_opt(2025, 3, 14).unwrap()]);
+    }
+
     #[test]
     fn test_export_dates() {
         let conn = setup_test_db();
-        add_date(&conn, NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()).unwrap();
-        add_date(&conn, NaiveDate::from_ymd_opt(2025, 1, 2).unwrap()).unwrap();
-        add_date(&conn, NaiveDate::from_ymd_opt(2025, 1, 3).unwrap()).unwrap();
+        add_date(&conn, NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(), None).unwrap();
+        add_date(&conn, NaiveDate::from_ymd_opt(2025, 1, 2).unwrap(), None).unwrap();
+        add_date(&conn, NaiveDate::from_ymd_opt(2025, 1, 3).unwrap(), None).unwrap();
 
-        let export = get_export(&conn).unwrap();
+        let facts = facts_on("2025-06-01");
+        let export = get_export(&conn, &facts, None).unwrap();
 
         assert_eq!(export, vec!["2025-01-01 :: 2025-01-03"]);
     }
+
+    #[test]
+    fn test_parse_rule_byday_without_freq_is_invalid() {
+        assert!(parse_rule("BYDAY=MO").is_err());
+    }
+
+    #[test]
+    fn test_parse_rule_defaults_interval_to_one() {
+        let rule = parse_rule("FREQ=DAILY").unwrap();
+        assert_eq!(rule.interval, 1);
+    }
+
+    #[test]
+    fn test_expand_weekly_rule_by_day() {
+        let rule = parse_rule("FREQ=WEEKLY;BYDAY=MO,WE").unwrap();
+        let dtstart = NaiveDate::from_ymd_opt(2025, 1, 6).unwrap(); // Monday
+        let window_end = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
+
+        let occurrences = expand_rule(&rule, dtstart, window_end);
+
+        assert_eq!(
+            occurrences,
+            vec![
+                NaiveDate::from_ymd_opt(2025, 1, 6).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 1, 8).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 1, 13).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 1, 15).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_monthly_rule_skips_interval_months() {
+        let rule = parse_rule("FREQ=MONTHLY;BYDAY=MO;INTERVAL=2").unwrap();
+        let dtstart = NaiveDate::from_ymd_opt(2025, 1, 6).unwrap(); // Monday
+        let window_end = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+
+        let occurrences = expand_rule(&rule, dtstart, window_end);
+
+        assert_eq!(
+            occurrences,
+            vec![
+                NaiveDate::from_ymd_opt(2025, 1, 6).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 1, 13).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 1, 20).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 1, 27).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 3, 3).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 3, 10).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 3, 17).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 3, 24).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 3, 31).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_rule_stops_at_count() {
+        let rule = parse_rule("FREQ=DAILY;COUNT=2").unwrap();
+        let dtstart = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let window_end = NaiveDate::from_ymd_opt(2025, 12, 31).unwrap();
+
+        let occurrences = expand_rule(&rule, dtstart, window_end);
+
+        assert_eq!(
+            occurrences,
+            vec![
+                NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 1, 2).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_unbounded_rule_clamps_to_window() {
+        let rule = parse_rule("FREQ=DAILY").unwrap();
+        let dtstart = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let window_end = NaiveDate::from_ymd_opt(2025, 1, 3).unwrap();
+
+        let occurrences = expand_rule(&rule, dtstart, window_end);
+
+        assert_eq!(occurrences.len(), 3);
+        assert_eq!(*occurrences.last().unwrap(), window_end);
+    }
+
+    #[test]
+    fn test_tagged_dates_unions_rules_and_explicit_days() {
+        let conn = setup_test_db();
+        add_date(&conn, NaiveDate::from_ymd_opt(2025, 1, 8).unwrap(), None).unwrap();
+        add_rule(
+            &conn,
+            "FREQ=WEEKLY;BYDAY=MO",
+            NaiveDate::from_ymd_opt(2025, 1, 6).unwrap(),
+            None,
+        )
+        .unwrap();
+
+        let facts = facts_on("2025-01-20");
+        let dates: Vec<NaiveDate> = tagged_dates(&conn, &facts, None)
+            .unwrap()
+            .into_iter()
+            .map(|(date, _)| date)
+            .collect();
+
+        assert_eq!(
+            dates,
+            vec![
+                NaiveDate::from_ymd_opt(2025, 1, 6).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 1, 8).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 1, 13).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 1, 20).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_category_filter_excludes_other_categories() {
+        let conn = setup_test_db();
+        add_date(
+            &conn,
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            Some("home"),
+        )
+        .unwrap();
+        add_date(
+            &conn,
+            NaiveDate::from_ymd_opt(2025, 1, 2).unwrap(),
+            Some("client"),
+        )
+        .unwrap();
+
+        let facts = facts_on("2025-06-01");
+        let export = get_export(&conn, &facts, Some("home")).unwrap();
+
+        assert_eq!(export, vec!["2025-01-01"]);
+    }
+
+    #[test]
+    fn test_get_export_does_not_merge_across_category_boundary() {
+        let conn = setup_test_db();
+        add_date(
+            &conn,
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            Some("home"),
+        )
+        .unwrap();
+        add_date(
+            &conn,
+            NaiveDate::from_ymd_opt(2025, 1, 2).unwrap(),
+            Some("client"),
+        )
+        .unwrap();
+
+        let facts = facts_on("2025-06-01");
+        let export = get_export(&conn, &facts, None).unwrap();
+
+        assert_eq!(export, vec!["2025-01-01", "2025-01-02"]);
+    }
+
+    #[test]
+    fn test_split_category() {
+        assert_eq!(
+            split_category("2025-01-03 @home"),
+            ("2025-01-03", Some("home"))
+        );
+        assert_eq!(split_category("2025-01-03"), ("2025-01-03", None));
+    }
+
+    #[test]
+    fn test_format_csv_has_header_and_one_date_per_row() {
+        let dates = vec![
+            (NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(), None),
+            (NaiveDate::from_ymd_opt(2025, 1, 2).unwrap(), None),
+        ];
+
+        assert_eq!(format_csv(&dates), "date\n2025-01-01\n2025-01-02\n");
+    }
+
+    #[test]
+    fn test_format_json_emits_range_objects() {
+        let ranges = vec![(
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 1, 3).unwrap(),
+        )];
+
+        let json = format_json(&ranges);
+
+        assert!(json.contains("\"start\": \"2025-01-01\""));
+        assert!(json.contains("\"end\": \"2025-01-03\""));
+    }
+
+    #[test]
+    fn test_format_ical_dtend_is_day_after_range_end() {
+        let ranges = vec![(
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 1, 3).unwrap(),
+        )];
+
+        let ical = format_ical(&ranges);
+
+        assert!(ical.contains("DTSTART;VALUE=DATE:20250101"));
+        assert!(ical.contains("DTEND;VALUE=DATE:20250104"));
+        assert!(ical.contains("SUMMARY:Home Office"));
+        assert!(ical.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ical.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn test_run_migrations_writes_database_version_on_init() {
+        let conn = setup_test_db();
+
+        assert_eq!(schema_version(&conn).unwrap(), MIGRATIONS.len() as i64);
+    }
+
+    #[test]
+    fn test_run_migrations_upgrades_a_pre_meta_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("CREATE TABLE home_office_days (date TEXT PRIMARY KEY)", [])
+            .unwrap();
+        conn.execute(
+            "INSERT INTO home_office_days (date) VALUES ('2025-01-01')",
+            [],
+        )
+        .unwrap();
+
+        create_table(&conn).unwrap();
+        run_migrations(&conn).unwrap();
+
+        assert_eq!(schema_version(&conn).unwrap(), MIGRATIONS.len() as i64);
+        let preserved: String = conn
+            .query_row("SELECT date FROM home_office_days", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(preserved, "2025-01-01");
+    }
 }